@@ -87,9 +87,12 @@ mod smallvec_impl {
 #[cfg(feature = "hashbrown")]
 mod hashbrown_impl {
     use crate::{Context, DeepSizeOf};
-    use core::mem::size_of;
 
-    // This is probably still incorrect, but it's better than before
+    // `allocation_size()` returns the exact size of the single heap block
+    // the table owns (the (K, V) bucket array, the one-control-byte-per-
+    // bucket metadata, and the replicated trailing control group used for
+    // SIMD probing, including alignment padding), so we no longer need to
+    // approximate it from `capacity()`.
     impl<K, V, S> DeepSizeOf for hashbrown::HashMap<K, V, S>
     where
         K: DeepSizeOf + Eq + std::hash::Hash,
@@ -99,12 +102,7 @@ mod hashbrown_impl {
         fn deep_size_of_children(&self, context: &mut Context) -> usize {
             self.iter().fold(0, |sum, (key, val)| {
                 sum + key.deep_size_of_children(context) + val.deep_size_of_children(context)
-            }) + self.capacity() * size_of::<(K, V)>()
-            // Buckets would be the more correct value, but there isn't
-            // an API for accessing that with hashbrown.
-            // I believe that hashbrown's HashTable is represented as
-            // an array of (K, V), with control bytes at the start/end
-            // that mark used/uninitialized buckets (?)
+            }) + self.allocation_size()
         }
     }
 
@@ -116,7 +114,7 @@ mod hashbrown_impl {
         fn deep_size_of_children(&self, context: &mut Context) -> usize {
             self.iter()
                 .fold(0, |sum, key| sum + key.deep_size_of_children(context))
-                + self.capacity() * size_of::<K>()
+                + self.allocation_size()
         }
     }
 }
@@ -127,11 +125,14 @@ mod indexmap_impl {
     use core::mem::size_of;
     use indexmap::{IndexMap, IndexSet};
 
-    // IndexMap uses a vec of buckets (usize, K, V) as backing, with
-    // a hashbrown::RawTable<usize> for lookups.  This method will
-    // consistently underestimate, because IndexMap::capacity will
-    // return the min of the capacity of the buckets list and the
-    // capacity of the raw table.
+    // IndexMap/IndexSet keep entries in an insertion-order `Vec`, and use a
+    // hashbrown `RawTable<usize>` purely for lookups. The Vec has no control
+    // bytes, so it is counted as `capacity() * size_of::<Bucket>()`; since
+    // `capacity()` is the smaller of the entries' and the table's capacities,
+    // this can undercount the entries Vec.
+    // indexmap keeps that raw table private, so unlike the `hashbrown` impls
+    // above we can't get its exact size via `allocation_size()`; approximate
+    // it as `capacity()` buckets of `usize` the way the original impl did.
     impl<K, V, S> DeepSizeOf for IndexMap<K, V, S>
     where
         K: DeepSizeOf,
@@ -141,8 +142,9 @@ mod indexmap_impl {
             let child_sizes = self.iter().fold(0, |sum, (key, val)| {
                 sum + key.deep_size_of_children(context) + val.deep_size_of_children(context)
             });
-            let map_size = self.capacity() * (size_of::<(usize, K, V)>() + size_of::<usize>());
-            child_sizes + map_size
+            let entries_size = self.capacity() * size_of::<(usize, K, V)>();
+            let indices_size = self.capacity() * size_of::<usize>();
+            child_sizes + entries_size + indices_size
         }
     }
     impl<K, S> DeepSizeOf for IndexSet<K, S>
@@ -153,8 +155,9 @@ mod indexmap_impl {
             let child_sizes = self
                 .iter()
                 .fold(0, |sum, key| sum + key.deep_size_of_children(context));
-            let map_size = self.capacity() * (size_of::<(usize, K, ())>() + size_of::<usize>());
-            child_sizes + map_size
+            let entries_size = self.capacity() * size_of::<(usize, K)>();
+            let indices_size = self.capacity() * size_of::<usize>();
+            child_sizes + entries_size + indices_size
         }
     }
 }
@@ -261,3 +264,109 @@ mod petgraph_impl {
         }
     }
 }
+
+#[cfg(feature = "internment")]
+mod internment_impl {
+    use crate::{Context, DeepSizeOf};
+    use core::mem::size_of;
+    use std::hash::Hash;
+
+    // `Intern`, `ArcIntern`, and `ArenaIntern` are handles into an interner
+    // (a global one, or a caller-owned `Arena` for `ArenaIntern`): many
+    // handles can point at the same backing allocation, so
+    // summing `deep_size_of_children` per handle would multiply the
+    // interned value's size by the number of live handles. Instead we key
+    // on the interned value's stable address and only count it the first
+    // time it's seen, via `Context::add_shared`.
+    impl<T: DeepSizeOf + Eq + Hash + Send + Sync + 'static> DeepSizeOf for internment::Intern<T> {
+        fn deep_size_of_children(&self, context: &mut Context) -> usize {
+            let ptr = &**self as *const T as *const ();
+            if context.add_shared(ptr) {
+                size_of::<T>() + (**self).deep_size_of_children(context)
+            } else {
+                0
+            }
+        }
+    }
+
+    impl<T: DeepSizeOf + Eq + Hash + Send + Sync + 'static> DeepSizeOf for internment::ArcIntern<T> {
+        fn deep_size_of_children(&self, context: &mut Context) -> usize {
+            let ptr = &**self as *const T as *const ();
+            if context.add_shared(ptr) {
+                size_of::<T>() + (**self).deep_size_of_children(context)
+            } else {
+                0
+            }
+        }
+    }
+
+    impl<'a, T: DeepSizeOf + Eq + Hash + 'a> DeepSizeOf for internment::ArenaIntern<'a, T> {
+        fn deep_size_of_children(&self, context: &mut Context) -> usize {
+            let ptr = &**self as *const T as *const ();
+            if context.add_shared(ptr) {
+                size_of::<T>() + (**self).deep_size_of_children(context)
+            } else {
+                0
+            }
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        #[derive(PartialEq, Eq, Hash)]
+        struct TestKey(u64);
+
+        impl DeepSizeOf for TestKey {
+            fn deep_size_of_children(&self, _context: &mut Context) -> usize {
+                0
+            }
+        }
+
+        #[test]
+        fn intern_deep_size_of_compiles_and_is_nonzero() {
+            let handle = internment::Intern::new(TestKey(7));
+            assert!(handle.deep_size_of() > 0);
+        }
+
+        #[test]
+        fn intern_aliased_handles_dedup_via_shared_context() {
+            let handle = internment::Intern::new(TestKey(42));
+            let alias = handle;
+            let mut context = Context::new();
+            let first = handle.deep_size_of_children(&mut context);
+            let second = alias.deep_size_of_children(&mut context);
+            assert!(first > 0);
+            assert_eq!(second, 0);
+        }
+    }
+}
+
+#[cfg(feature = "thin-vec")]
+mod thin_vec_impl {
+    use crate::{Context, DeepSizeOf};
+    use core::mem::size_of;
+    use thin_vec::ThinVec;
+
+    // A `ThinVec<T>` is a single machine word on the stack pointing at one
+    // heap allocation laid out as `[capacity: usize, length: usize,
+    // ...elements]`. An empty, never-allocated `ThinVec` instead points at
+    // a shared static empty-header sentinel, which must not be counted.
+    impl<T> DeepSizeOf for ThinVec<T>
+    where
+        T: DeepSizeOf,
+    {
+        fn deep_size_of_children(&self, context: &mut Context) -> usize {
+            if self.capacity() == 0 {
+                return 0;
+            }
+            let header_size = 2 * size_of::<usize>();
+            let elements_size = self.capacity() * size_of::<T>();
+            let child_sizes = self
+                .iter()
+                .fold(0, |sum, elem| sum + elem.deep_size_of_children(context));
+            header_size + elements_size + child_sizes
+        }
+    }
+}
@@ -0,0 +1,141 @@
+//! A crate to estimate the amount of heap memory used by a value, walking
+//! through its owned allocations rather than just reporting `size_of`.
+//!
+//! This file implements the `DeepSizeOf` trait for `Arc` and `Rc` only;
+//! other standard library types (`Vec`, `String`, slices, ...) have no impl
+//! here. Impls for common crates are available behind feature flags; see
+//! `external_impls` for the full list.
+
+use core::mem::size_of;
+use std::collections::HashSet;
+
+#[cfg(any(
+    feature = "slotmap",
+    feature = "slab",
+    feature = "arrayvec",
+    feature = "smallvec",
+    feature = "hashbrown",
+    feature = "indexmap",
+    feature = "chrono",
+    feature = "tokio_net",
+    feature = "actix",
+    feature = "cpe",
+    feature = "petgraph",
+    feature = "internment",
+    feature = "thin-vec",
+))]
+mod external_impls;
+
+/// Context for a single `deep_size_of` walk.
+///
+/// This is threaded through every call to `deep_size_of_children` so that
+/// shared allocations (`Rc`, `Arc`, and similar) are only counted once, no
+/// matter how many handles to them are visited during the walk.
+pub struct Context {
+    visited: HashSet<*const ()>,
+}
+
+impl Context {
+    pub fn new() -> Context {
+        Context {
+            visited: HashSet::new(),
+        }
+    }
+
+    /// Records `ptr` as visited, returning `true` if it was not already
+    /// present. Used internally to dedup `Rc`/`Arc` allocations.
+    fn add(&mut self, ptr: *const ()) -> bool {
+        self.visited.insert(ptr)
+    }
+
+    /// Records a raw pointer to a shared or interned allocation, returning
+    /// `true` if this is the first time it's been seen during this walk.
+    ///
+    /// `Context`'s pointer-visited set is otherwise private to this crate's
+    /// own `Rc`/`Arc` handling, so downstream crates with their own shared
+    /// or interned containers (interning arenas, columnar region allocators,
+    /// custom `Arc`-likes) had no way to avoid double-counting a backing
+    /// allocation shared by many handles. This exposes that same bookkeeping:
+    ///
+    /// ```
+    /// # use deepsize::{Context, DeepSizeOf};
+    /// # use core::mem::size_of;
+    /// struct MyInterned<T>(*const T);
+    /// impl<T: DeepSizeOf> DeepSizeOf for MyInterned<T> {
+    ///     fn deep_size_of_children(&self, context: &mut Context) -> usize {
+    ///         if context.add_shared(self.0 as *const ()) {
+    ///             size_of::<T>() + unsafe { (*self.0).deep_size_of_children(context) }
+    ///         } else {
+    ///             0
+    ///         }
+    ///     }
+    /// }
+    /// ```
+    pub fn add_shared(&mut self, ptr: *const ()) -> bool {
+        self.add(ptr)
+    }
+
+    /// Checks whether `ptr` has already been recorded, without inserting it.
+    pub fn contains_shared(&self, ptr: *const ()) -> bool {
+        self.visited.contains(&ptr)
+    }
+}
+
+impl Default for Context {
+    fn default() -> Self {
+        Context::new()
+    }
+}
+
+/// Trait for estimating the amount of heap memory owned by a value.
+pub trait DeepSizeOf {
+    /// Returns an estimate of the total size of `self`, including the
+    /// `size_of::<Self>()` stack footprint and all heap allocations it owns.
+    fn deep_size_of(&self) -> usize
+    where
+        Self: Sized,
+    {
+        size_of::<Self>() + self.deep_size_of_children(&mut Context::new())
+    }
+
+    /// Returns an estimate of the heap memory owned by `self`, not including
+    /// `self`'s own stack footprint. Shared allocations already recorded in
+    /// `context` are skipped to avoid double-counting.
+    fn deep_size_of_children(&self, context: &mut Context) -> usize;
+}
+
+impl<T: ?Sized + DeepSizeOf> DeepSizeOf for std::sync::Arc<T> {
+    fn deep_size_of_children(&self, context: &mut Context) -> usize {
+        let ptr = std::sync::Arc::as_ptr(self) as *const ();
+        if context.add(ptr) {
+            core::mem::size_of_val(&**self) + (**self).deep_size_of_children(context)
+        } else {
+            0
+        }
+    }
+}
+
+impl<T: ?Sized + DeepSizeOf> DeepSizeOf for std::rc::Rc<T> {
+    fn deep_size_of_children(&self, context: &mut Context) -> usize {
+        let ptr = std::rc::Rc::as_ptr(self) as *const ();
+        if context.add(ptr) {
+            core::mem::size_of_val(&**self) + (**self).deep_size_of_children(context)
+        } else {
+            0
+        }
+    }
+}
+
+/// Implements `DeepSizeOf` for types whose `deep_size_of_children` is
+/// always the same fixed value (commonly `0`, for types that own no heap
+/// allocations).
+#[macro_export]
+macro_rules! known_deep_size {
+    ($size:expr; $($({$($gen:tt)*})? $type:ty),+) => {
+        $(impl $(<$($gen)*>)? $crate::DeepSizeOf for $type {
+            fn deep_size_of_children(&self, _context: &mut $crate::Context) -> usize {
+                $size
+            }
+        })+
+    };
+}